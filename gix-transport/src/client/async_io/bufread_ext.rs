@@ -1,10 +1,12 @@
 use std::{
+    cell::RefCell,
     io,
     ops::{Deref, DerefMut},
+    rc::Rc,
 };
 
 use async_trait::async_trait;
-use futures_io::{AsyncBufRead, AsyncRead};
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
 
 use crate::{
     client::{Error, MessageKind},
@@ -139,3 +141,32 @@ impl<'a, T: AsyncRead + Unpin> ExtendedBufRead<'a> for WithSidebands<'a, T, Hand
         })
     }
 }
+
+/// Drain `reader` by writing the pack data of band 1 into `pack_sink` and passing the progress messages of band 2 to
+/// `progress`, returning the amount of bytes written on success.
+///
+/// An error message on band 3 stops the operation and returns it as [`Error::Sideband`].
+pub async fn stream_response<'a>(
+    mut reader: Box<dyn ExtendedBufRead<'a> + Unpin + 'a>,
+    pack_sink: impl AsyncWrite + Unpin,
+    mut progress: impl FnMut(&[u8]) + 'a,
+) -> Result<u64, Error> {
+    let remote_error = Rc::new(RefCell::new(None));
+    reader.set_progress_handler(Some(Box::new({
+        let remote_error = remote_error.clone();
+        move |is_err, text| {
+            if is_err {
+                *remote_error.borrow_mut() = Some(text.into());
+                ProgressAction::Break(())
+            } else {
+                progress(text);
+                ProgressAction::Continue(())
+            }
+        }
+    })));
+    let res = futures_lite::io::copy(reader, pack_sink).await;
+    if let Some(message) = remote_error.borrow_mut().take() {
+        return Err(Error::Sideband { message });
+    }
+    Ok(res?)
+}
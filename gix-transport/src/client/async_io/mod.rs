@@ -1,11 +1,11 @@
 mod bufread_ext;
-pub use bufread_ext::{ExtendedBufRead, HandleProgress, ReadlineBufRead};
+pub use bufread_ext::{stream_response, ExtendedBufRead, HandleProgress, ReadlineBufRead};
 
 mod request;
 pub use request::RequestWriter;
 
 mod traits;
-pub use traits::{SetServiceResponse, Transport, TransportV2Ext};
+pub use traits::{SetServiceResponse, Transport, TransportExt, TransportV2Ext};
 
 ///
 pub mod connect;
@@ -1,6 +1,8 @@
 use std::{
+    cell::RefCell,
     io,
     ops::{Deref, DerefMut},
+    rc::Rc,
 };
 
 use gix_packetline::{blocking_io::WithSidebands, read::ProgressAction, PacketLineRef};
@@ -129,3 +131,32 @@ impl<'a, T: io::Read> ExtendedBufRead<'a> for WithSidebands<'a, T, HandleProgres
         })
     }
 }
+
+/// Drain `reader` by writing the pack data of band 1 into `pack_sink` and passing the progress messages of band 2 to
+/// `progress`, returning the amount of bytes written on success.
+///
+/// An error message on band 3 stops the operation and returns it as [`Error::Sideband`].
+pub fn stream_response<'a>(
+    mut reader: Box<dyn ExtendedBufRead<'a> + Unpin + 'a>,
+    mut pack_sink: impl io::Write,
+    mut progress: impl FnMut(&[u8]) + 'a,
+) -> Result<u64, Error> {
+    let remote_error = Rc::new(RefCell::new(None));
+    reader.set_progress_handler(Some(Box::new({
+        let remote_error = remote_error.clone();
+        move |is_err, text| {
+            if is_err {
+                *remote_error.borrow_mut() = Some(text.into());
+                ProgressAction::Break(())
+            } else {
+                progress(text);
+                ProgressAction::Continue(())
+            }
+        }
+    })));
+    let res = io::copy(&mut reader, &mut pack_sink);
+    if let Some(message) = remote_error.borrow_mut().take() {
+        return Err(Error::Sideband { message });
+    }
+    Ok(res?)
+}
@@ -116,6 +116,30 @@ impl client::TransportWithoutIO for SpawnProcessOnDemand {
         true
     }
 
+    fn supports_command_pipelining(&self) -> bool {
+        self.negotiated_capabilities()
+            .map_or(self.desired_version, |(protocol, _)| protocol)
+            == Protocol::V2
+    }
+
+    fn negotiated_capabilities(&self) -> Option<(Protocol, &client::Capabilities)> {
+        self.connection.as_ref()?.negotiated_capabilities()
+    }
+
+    fn requested_protocol(&self, token: client::traits::private::Token) -> Option<Protocol> {
+        self.connection.as_ref()?.requested_protocol(token)
+    }
+
+    fn selected_capabilities(&self, token: client::traits::private::Token) -> Option<&[BString]> {
+        self.connection.as_ref()?.selected_capabilities(token)
+    }
+
+    fn set_selected_capabilities(&mut self, token: client::traits::private::Token, capabilities: &[BString]) {
+        if let Some(connection) = self.connection.as_mut() {
+            connection.set_selected_capabilities(token, capabilities);
+        }
+    }
+
     fn configure(&mut self, _config: &dyn Any) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         Ok(())
     }
@@ -297,6 +321,23 @@ pub fn connect(
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn spawned_processes_support_command_pipelining_in_v2() {
+        use crate::{
+            client::{blocking_io::ssh, TransportWithoutIO},
+            Protocol,
+        };
+        let local = super::connect("/path/to/repo", Protocol::V2, false).expect("infallible");
+        assert!(local.supports_command_pipelining(), "pipes persist across requests");
+
+        let url = gix_url::parse("ssh://host.xy/repo".into()).expect("valid url");
+        let remote = ssh::connect(url, Protocol::V2, Default::default(), false).expect("parse success");
+        assert!(remote.supports_command_pipelining(), "so does the ssh connection");
+
+        let local = super::connect("/path/to/repo", Protocol::V1, false).expect("infallible");
+        assert!(!local.supports_command_pipelining(), "V1 can't pipeline commands");
+    }
+
     mod ssh {
         mod connect {
             use crate::{client::blocking_io::ssh, Protocol};
@@ -0,0 +1,120 @@
+use bstr::ByteSlice;
+
+/// The kind of file listing alternate object stores of a repository.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// `objects/info/http-alternates`, which may list absolute URLs along with paths.
+    Http,
+    /// `objects/info/alternates`, which only lists paths as seen by the server.
+    Plain,
+}
+
+impl Kind {
+    /// The path of the file relative to the repository root.
+    pub fn path(&self) -> &'static str {
+        match self {
+            Kind::Http => "objects/info/http-alternates",
+            Kind::Plain => "objects/info/alternates",
+        }
+    }
+}
+
+/// Parse the alternates file `input` of the given `kind` into the base URLs of the repositories
+/// owning the alternate object stores, with paths resolved against `repo_url`.
+///
+/// Blank lines and lines starting with `#` are ignored, as are entries that don't end in `objects`,
+/// absolute URLs in [plain](Kind::Plain) files and relative paths leaving the server root.
+pub fn parse(repo_url: &str, kind: Kind, input: &[u8]) -> Vec<String> {
+    let repo_url = repo_url.trim_end_matches('/');
+    input
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().to_str().ok()?;
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let objects = if line.starts_with("http://") || line.starts_with("https://") {
+                (kind == Kind::Http).then(|| line.to_owned())?
+            } else if line.starts_with('/') {
+                format!("{}{line}", server_root(repo_url))
+            } else {
+                resolve_relative(repo_url, line)?
+            };
+            objects
+                .trim_end_matches('/')
+                .strip_suffix("/objects")
+                .map(ToOwned::to_owned)
+        })
+        .collect()
+}
+
+/// Return `scheme://host[:port]` of `url`.
+fn server_root(url: &str) -> &str {
+    let host_start = url.find("://").map_or(0, |pos| pos + 3);
+    url[host_start..].find('/').map_or(url, |pos| &url[..host_start + pos])
+}
+
+/// Resolve `path` relative to the `objects` directory of `repo_url`.
+fn resolve_relative(repo_url: &str, mut path: &str) -> Option<String> {
+    let root = server_root(repo_url);
+    let mut components: Vec<_> = repo_url[root.len()..]
+        .split('/')
+        .filter(|c| !c.is_empty())
+        .chain(Some("objects"))
+        .collect();
+    while let Some(rest) = path.strip_prefix("../") {
+        components.pop()?;
+        path = rest;
+    }
+    components.push(path);
+    Some(format!("{root}/{}", components.join("/")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Kind};
+
+    const REPO: &str = "https://example.com/pub/scm/linux.git";
+
+    #[test]
+    fn http_alternates_with_absolute_and_relative_urls() {
+        let input = b"# a comment\n\
+            https://mirror.example.com/linus.git/objects\n\
+            \n\
+            ../../linus.git/objects\n\
+            /other/repo.git/objects/\n\
+            http://legacy.example.com/repo.git/objects\n";
+        assert_eq!(
+            parse(REPO, Kind::Http, input),
+            [
+                "https://mirror.example.com/linus.git",
+                "https://example.com/pub/scm/linus.git",
+                "https://example.com/other/repo.git",
+                "http://legacy.example.com/repo.git",
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_alternates_ignore_urls() {
+        let input = b"../../linus.git/objects\r\n  # indented comment\n\t\nhttps://mirror.example.com/linus.git/objects\n/srv/git/base.git/objects";
+        assert_eq!(
+            parse(&format!("{REPO}/"), Kind::Plain, input),
+            [
+                "https://example.com/pub/scm/linus.git",
+                "https://example.com/srv/git/base.git"
+            ],
+            "trailing slashes of the repository URL and a missing trailing newline don't matter"
+        );
+    }
+
+    #[test]
+    fn invalid_entries_are_skipped() {
+        let input = b"../../../../../escape.git/objects\n../../not-an-object-store\n";
+        assert_eq!(
+            parse(REPO, Kind::Http, input),
+            Vec::<String>::new(),
+            "leaving the server root or not pointing to 'objects' is ignored"
+        );
+    }
+}
@@ -7,7 +7,7 @@ use std::{
 };
 
 use base64::Engine;
-use bstr::BStr;
+use bstr::{BStr, BString};
 pub use traits::{Error, GetResponse, Http, PostBodyDataKind, PostResponse};
 
 use crate::{
@@ -20,7 +20,7 @@ use crate::{
             ExtendedBufRead, HandleProgress, RequestWriter, SetServiceResponse,
         },
         capabilities::blocking_recv::Handshake,
-        MessageKind,
+        Capabilities, MessageKind,
     },
     packetline::{blocking_io::StreamingPeekableIter, PacketLineRef},
     Protocol, Service,
@@ -39,6 +39,9 @@ pub mod reqwest;
 
 mod traits;
 
+///
+pub mod alternates;
+
 ///
 pub mod options {
     /// A function to authenticate a URL.
@@ -223,6 +226,12 @@ pub struct Transport<H: Http> {
     service: Option<Service>,
     line_provider: Option<StreamingPeekableIter<H::ResponseBody>>,
     identity: Option<gix_sec::identity::Account>,
+    cached_capabilities: Option<Capabilities>,
+    seeded_capabilities: Option<(Service, Capabilities)>,
+    negotiated_capabilities: Option<Capabilities>,
+    selected_capabilities: Option<Vec<BString>>,
+    alternates: Option<Vec<String>>,
+    content_type: Option<String>,
     trace: bool,
 }
 
@@ -248,9 +257,30 @@ impl<H: Http> Transport<H> {
             http,
             line_provider: None,
             identity,
+            cached_capabilities: None,
+            seeded_capabilities: None,
+            negotiated_capabilities: None,
+            selected_capabilities: None,
+            alternates: None,
+            content_type: None,
             trace,
         }
     }
+
+    /// Seed this instance with `capabilities` the remote advertised for `service`, as previously obtained from
+    /// [`cached_capabilities()`](client::TransportWithoutIO::cached_capabilities()) of a transport to the same remote,
+    /// so the next handshake for the same `service` can skip the capability advertisement and save a round-trip.
+    ///
+    /// This only takes effect if [`Protocol::V2`] is desired, as V1 requires the advertisement to obtain the refs.
+    /// The remote may have changed its capabilities since, which will only be noticed once a request fails.
+    ///
+    /// Note that skipping the advertisement also skips the authentication it would have triggered, so a remote requiring
+    /// credentials will reject the first request instead, which isn't retried with credentials like a failed handshake
+    /// would be. Thus, only seed transports to authenticated remotes if their [identity](Self::identity()) is set.
+    pub fn with_cached_capabilities(mut self, service: Service, capabilities: Option<Capabilities>) -> Self {
+        self.seeded_capabilities = capabilities.map(|capabilities| (service, capabilities));
+        self
+    }
 }
 
 impl<H: Http> Transport<H> {
@@ -258,6 +288,53 @@ impl<H: Http> Transport<H> {
     pub fn identity(&self) -> Option<&gix_sec::identity::Account> {
         self.identity.as_ref()
     }
+
+    /// Return the `Content-Type` of the response to the last handshake, if it had one.
+    ///
+    /// For the 'smart' protocol, it must be `application/x-<service>-advertisement`, whereas anything else fails the
+    /// handshake with [`Error::UnexpectedContentType`], which typically happens if a proxy responds with an HTML error page.
+    pub fn response_content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Return the base URLs of repositories with alternate object stores, which 'dumb' http clients can consult
+    /// for objects missing in the remote repository.
+    ///
+    /// They are read from [`objects/info/http-alternates`](alternates::Kind::Http), falling back to
+    /// [`objects/info/alternates`](alternates::Kind::Plain), when first called and cached thereafter.
+    /// If neither file exists, the list is empty, while any other failure is returned and the files are tried again
+    /// on the next call.
+    pub fn alternates(&mut self) -> Result<&[String], client::Error> {
+        if self.alternates.is_none() {
+            let mut alternates = Vec::new();
+            for kind in [alternates::Kind::Http, alternates::Kind::Plain] {
+                match self.get_file(kind.path()) {
+                    Ok(data) => {
+                        alternates = alternates::parse(&self.url, kind, &data);
+                        break;
+                    }
+                    Err(client::Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(err),
+                }
+            }
+            self.alternates = Some(alternates);
+        }
+        Ok(self.alternates.as_deref().expect("set above"))
+    }
+
+    fn get_file(&mut self, path: &str) -> Result<Vec<u8>, client::Error> {
+        let url = append_url(&self.url, path);
+        let static_headers = [Cow::Borrowed(self.user_agent_header)];
+        let mut dynamic_headers = Vec::<Cow<'_, str>>::new();
+        self.add_basic_auth_if_present(&mut dynamic_headers)?;
+        let GetResponse { mut headers, mut body } =
+            self.http
+                .get(&url, &self.url, static_headers.iter().chain(&dynamic_headers))?;
+        std::io::copy(&mut headers, &mut std::io::sink())?;
+        let mut data = Vec::new();
+        body.read_to_end(&mut data)?;
+        Ok(data)
+    }
 }
 
 #[cfg(any(feature = "http-client-curl", feature = "http-client-reqwest"))]
@@ -272,21 +349,24 @@ impl<H: Http + Default> Transport<H> {
 }
 
 impl<H: Http> Transport<H> {
-    fn check_content_type(service: Service, kind: &str, headers: <H as Http>::Headers) -> Result<(), client::Error> {
-        let wanted_content_type = format!("application/x-{}-{}", service.as_str(), kind);
-        if !headers.lines().collect::<Result<Vec<_>, _>>()?.iter().any(|l| {
-            let mut tokens = l.split(':');
-            tokens.next().zip(tokens.next()).is_some_and(|(name, value)| {
-                name.eq_ignore_ascii_case("content-type") && value.trim() == wanted_content_type
-            })
-        }) {
-            return Err(client::Error::Http(Error::Detail {
-                description: format!(
-                    "Didn't find '{wanted_content_type}' header to indicate 'smart' protocol, and 'dumb' protocol is not supported."
-                ),
-            }));
+    fn content_type(headers: <H as Http>::Headers) -> Result<Option<String>, client::Error> {
+        Ok(headers.lines().collect::<Result<Vec<_>, _>>()?.iter().find_map(|l| {
+            l.split_once(':')
+                .filter(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+                .map(|(_, value)| value.trim().to_owned())
+        }))
+    }
+
+    fn check_content_type(service: Service, kind: &str, content_type: Option<&str>) -> Result<(), client::Error> {
+        let expected = format!("application/x-{}-{}", service.as_str(), kind);
+        // Parameters like `charset` don't matter for binary data.
+        match content_type.and_then(|value| value.split(';').next()) {
+            Some(media_type) if media_type.trim().eq_ignore_ascii_case(&expected) => Ok(()),
+            _ => Err(client::Error::Http(Error::UnexpectedContentType {
+                expected,
+                got: content_type.map(ToOwned::to_owned),
+            })),
         }
-        Ok(())
     }
 
     #[allow(clippy::unnecessary_wraps, unknown_lints)]
@@ -335,6 +415,33 @@ impl<H: Http> client::TransportWithoutIO for Transport<H> {
         false
     }
 
+    fn supports_command_pipelining(&self) -> bool {
+        false
+    }
+
+    fn cached_capabilities(&self) -> Option<&Capabilities> {
+        self.cached_capabilities.as_ref()
+    }
+
+    fn negotiated_capabilities(&self) -> Option<(Protocol, &Capabilities)> {
+        self.negotiated_capabilities
+            .as_ref()
+            .map(|capabilities| (self.actual_version, capabilities))
+    }
+
+    fn requested_protocol(&self, _: client::traits::private::Token) -> Option<Protocol> {
+        Some(self.desired_version)
+    }
+
+    fn selected_capabilities(&self, _: client::traits::private::Token) -> Option<&[BString]> {
+        self.selected_capabilities.as_deref()
+    }
+
+    fn set_selected_capabilities(&mut self, _: client::traits::private::Token, capabilities: &[BString]) {
+        self.selected_capabilities
+            .get_or_insert_with(|| capabilities.to_owned());
+    }
+
     fn configure(&mut self, config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         self.http.configure(config)
     }
@@ -346,6 +453,26 @@ impl<H: Http> blocking_io::Transport for Transport<H> {
         service: Service,
         extra_parameters: &'a [(&'a str, Option<&'a str>)],
     ) -> Result<SetServiceResponse<'_>, client::Error> {
+        if self.desired_version == Protocol::V2 {
+            if let Some((_, capabilities)) = self
+                .seeded_capabilities
+                .take()
+                .filter(|(seeded_service, _)| *seeded_service == service)
+            {
+                // V2 is stateless, so there is nothing the advertisement would tell us that we don't know already.
+                self.actual_version = Protocol::V2;
+                self.content_type = None;
+                self.cached_capabilities = Some(capabilities.clone());
+                self.service = Some(service);
+                self.negotiated_capabilities = Some(capabilities.clone());
+                self.selected_capabilities = None;
+                return Ok(SetServiceResponse {
+                    actual_protocol: Protocol::V2,
+                    capabilities,
+                    refs: None,
+                });
+            }
+        }
         let url = append_url(self.url.as_ref(), &format!("info/refs?service={}", service.as_str()));
         let static_headers = [Cow::Borrowed(self.user_agent_header)];
         let mut dynamic_headers = Vec::<Cow<'_, str>>::new();
@@ -375,7 +502,8 @@ impl<H: Http> blocking_io::Transport for Transport<H> {
         let GetResponse { headers, body } =
             self.http
                 .get(url.as_ref(), &self.url, static_headers.iter().chain(&dynamic_headers))?;
-        <Transport<H>>::check_content_type(service, "advertisement", headers)?;
+        self.content_type = <Transport<H>>::content_type(headers)?;
+        <Transport<H>>::check_content_type(service, "advertisement", self.content_type.as_deref())?;
 
         let line_reader = self
             .line_provider
@@ -410,6 +538,9 @@ impl<H: Http> blocking_io::Transport for Transport<H> {
         } = Handshake::from_lines_with_version_detection(line_reader)?;
         self.actual_version = actual_protocol;
         self.service = Some(service);
+        self.cached_capabilities = (actual_protocol == Protocol::V2).then(|| capabilities.clone());
+        self.negotiated_capabilities = Some(capabilities.clone());
+        self.selected_capabilities = None;
         Ok(SetServiceResponse {
             actual_protocol,
             capabilities,
@@ -449,11 +580,16 @@ impl<H: Http> blocking_io::Transport for Transport<H> {
             static_headers.iter().chain(&dynamic_headers),
             write_mode.into(),
         )?;
-        let line_provider = self
-            .line_provider
-            .as_mut()
-            .expect("handshake to have been called first");
-        line_provider.replace(body);
+        let line_provider = match self.line_provider.take() {
+            Some(mut line_provider) => {
+                line_provider.replace(body);
+                self.line_provider.insert(line_provider)
+            }
+            // The handshake was skipped thanks to cached capabilities.
+            None => self
+                .line_provider
+                .insert(StreamingPeekableIter::new(body, &[PacketLineRef::Flush], self.trace)),
+        };
         Ok(RequestWriter::new_from_bufread(
             post_body,
             Box::new(HeadersThenBody::<H, _> {
@@ -477,7 +613,9 @@ struct HeadersThenBody<H: Http, B: Unpin> {
 impl<H: Http, B: Unpin> HeadersThenBody<H, B> {
     fn handle_headers(&mut self) -> std::io::Result<()> {
         if let Some(headers) = self.headers.take() {
-            <Transport<H>>::check_content_type(self.service, "result", headers).map_err(std::io::Error::other)?;
+            let content_type = <Transport<H>>::content_type(headers).map_err(std::io::Error::other)?;
+            <Transport<H>>::check_content_type(self.service, "result", content_type.as_deref())
+                .map_err(std::io::Error::other)?;
         }
         Ok(())
     }
@@ -8,7 +8,7 @@ pub mod file;
 pub mod http;
 
 mod bufread_ext;
-pub use bufread_ext::{ExtendedBufRead, HandleProgress, ReadlineBufRead};
+pub use bufread_ext::{stream_response, ExtendedBufRead, HandleProgress, ReadlineBufRead};
 
 mod request;
 pub use request::RequestWriter;
@@ -17,4 +17,5 @@ pub use request::RequestWriter;
 pub mod ssh;
 
 mod traits;
-pub use traits::{SetServiceResponse, Transport, TransportV2Ext};
+pub(crate) use traits::ls_refs;
+pub use traits::{SetServiceResponse, Transport, TransportExt, TransportV2Ext};
@@ -5,6 +5,10 @@ use bstr::BString;
 use crate::{
     client::{
         blocking_io::{request::RequestWriter, ExtendedBufRead, ReadlineBufRead},
+        non_io_types::{
+            object_info,
+            refs::{self, Ref},
+        },
         Capabilities, Error, MessageKind, TransportWithoutIO, WriteMode,
     },
     Protocol, Service,
@@ -101,6 +105,9 @@ pub trait TransportV2Ext {
     /// The `capabilities` were communicated during the handshake.
     /// If `trace` is `true`, then all packetlines written and received will be traced using facilities provided by the `gix_trace` crate.
     ///
+    /// If a V2 handshake was performed, commands which aren't listed by
+    /// [`supported_commands()`](crate::client::TransportWithoutIO::supported_commands()) fail with [`Error::UnsupportedCommand`].
+    ///
     /// _Note:_ panics if [handshake][Transport::handshake()] wasn't performed beforehand.
     fn invoke<'a>(
         &mut self,
@@ -119,13 +126,24 @@ impl<T: Transport> TransportV2Ext for T {
         arguments: Option<impl Iterator<Item = BString>>,
         trace: bool,
     ) -> Result<Box<dyn ExtendedBufRead<'_> + Unpin + '_>, Error> {
+        if matches!(self.negotiated_capabilities(), Some((Protocol::V2, _)))
+            && !self.supported_commands().iter().any(|c| c == command)
+        {
+            return Err(Error::UnsupportedCommand {
+                command: command.into(),
+            });
+        }
+        let capabilities: Vec<BString> = capabilities
+            .map(|(name, value)| match value {
+                Some(value) => format!("{name}={}", value.as_ref()).into(),
+                None => name.into(),
+            })
+            .collect();
+        self.set_selected_capabilities(crate::client::traits::private::Token, &capabilities);
         let mut writer = self.request(WriteMode::OneLfTerminatedLinePerWriteCall, MessageKind::Flush, trace)?;
         writer.write_all(format!("command={command}").as_bytes())?;
-        for (name, value) in capabilities {
-            match value {
-                Some(value) => writer.write_all(format!("{name}={}", value.as_ref()).as_bytes()),
-                None => writer.write_all(name.as_bytes()),
-            }?;
+        for capability in &capabilities {
+            writer.write_all(capability)?;
         }
         if let Some(arguments) = arguments {
             writer.write_message(MessageKind::Delimiter)?;
@@ -136,3 +154,134 @@ impl<T: Transport> TransportV2Ext for T {
         Ok(writer.into_read()?)
     }
 }
+
+/// An extension trait to add protocol-agnostic conveniences to everything implementing [`Transport`].
+pub trait TransportExt {
+    /// Perform a handshake with the [upload-pack service](Service::UploadPack) and return the object id the remote `HEAD`
+    /// points to, or `None` if the remote repository is empty.
+    ///
+    /// In V1, `HEAD` is taken from the ref advertisement, which is consumed entirely, while in V2 an `ls-refs` command
+    /// limited to `HEAD` is sent.
+    /// If a V2 handshake was performed already, only the `ls-refs` command is sent. After a V1 handshake, stateful
+    /// connections fail with [`Error::RefAdvertisementConsumed`] as the advertisement can't be read again, while
+    /// stateless ones perform another handshake.
+    fn head_oid(&mut self) -> Result<Option<gix_hash::ObjectId>, Error>;
+
+    /// Perform the cheapest valid exchange with the remote and return the time it took, which is useful for liveness checks.
+    ///
+    /// This is a [`head_oid()`](TransportExt::head_oid()) call whose result is discarded, which drains everything
+    /// the remote sends so the transport can be used for further requests afterwards.
+    /// In V2 it can be called repeatedly, while stateful V1 connections fail after the first call.
+    fn ping(&mut self) -> Result<std::time::Duration, Error>;
+
+    /// Perform a handshake with the [upload-pack service](Service::UploadPack) and return `true` if the remote has
+    /// the object with `id`, without downloading it.
+    ///
+    /// This uses the V2 `object-info` command and fails with [`Error::UnsupportedCommand`] if the remote doesn't
+    /// advertise it, which is always the case in V1.
+    /// If a V2 handshake was performed already, only the `object-info` command is sent.
+    fn has_object(&mut self, id: gix_hash::ObjectId) -> Result<bool, Error>;
+}
+
+impl<T: Transport> TransportExt for T {
+    fn head_oid(&mut self) -> Result<Option<gix_hash::ObjectId>, Error> {
+        Ok(ls_refs(self, &["symrefs", "ref-prefix HEAD"])?
+            .into_iter()
+            .find(|r| r.name == "HEAD")
+            .map(|r| r.object))
+    }
+
+    fn ping(&mut self) -> Result<std::time::Duration, Error> {
+        let start = std::time::Instant::now();
+        self.head_oid()?;
+        Ok(start.elapsed())
+    }
+
+    fn has_object(&mut self, id: gix_hash::ObjectId) -> Result<bool, Error> {
+        if matches!(self.negotiated_capabilities(), Some((protocol, _)) if protocol != Protocol::V2) {
+            return Err(Error::UnsupportedCommand {
+                command: "object-info".into(),
+            });
+        }
+        let (capabilities, _refs) = upload_pack_handshake(self)?;
+        if !capabilities.contains("object-info") {
+            return Err(Error::UnsupportedCommand {
+                command: "object-info".into(),
+            });
+        }
+
+        let object_format = refs::object_format(&capabilities);
+        let mut lines = self.invoke(
+            "object-info",
+            object_format
+                .as_deref()
+                .map(|format| ("object-format", Some(format)))
+                .into_iter(),
+            Some([BString::from("size"), format!("oid {id}").into()].into_iter()),
+            false,
+        )?;
+        let mut found = false;
+        while let Some(line) = lines.readline() {
+            if let Some(line) = line??.as_bstr() {
+                found |= object_info::reports_size_of(line, &id);
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// Return the capabilities of a V2 session negotiated previously, or perform a handshake with the
+/// [upload-pack service](Service::UploadPack) and return the advertised capabilities, along with the refs of the
+/// ref advertisement in V1.
+///
+/// Stateful connections can't perform another handshake after a V1 one, and fail with
+/// [`Error::RefAdvertisementConsumed`] instead.
+fn upload_pack_handshake<T: Transport>(transport: &mut T) -> Result<(Capabilities, Option<Vec<Ref>>), Error> {
+    match transport.negotiated_capabilities() {
+        Some((Protocol::V2, capabilities)) => return Ok((capabilities.clone(), None)),
+        Some(_) if transport.connection_persists_across_multiple_requests() => {
+            return Err(Error::RefAdvertisementConsumed)
+        }
+        _ => {}
+    }
+    let SetServiceResponse { capabilities, refs, .. } = transport.handshake(Service::UploadPack, &[])?;
+    let refs = match refs {
+        Some(mut lines) => {
+            let mut out = Vec::new();
+            while let Some(line) = lines.readline() {
+                if let Some(line) = line??.as_bstr() {
+                    refs::push_ref(line, &mut out)?;
+                }
+            }
+            Some(out)
+        }
+        None => None,
+    };
+    Ok((capabilities, refs))
+}
+
+/// Return the refs of the V1 ref advertisement, or send an `ls-refs` command with `arguments` in V2 and return the
+/// refs it lists, performing a handshake only if needed.
+pub(crate) fn ls_refs<T: Transport>(transport: &mut T, arguments: &[&str]) -> Result<Vec<Ref>, Error> {
+    let object_format = match upload_pack_handshake(transport)? {
+        (_, Some(refs)) => return Ok(refs),
+        (capabilities, None) => refs::object_format(&capabilities),
+    };
+
+    let mut lines = transport.invoke(
+        "ls-refs",
+        object_format
+            .as_deref()
+            .map(|format| ("object-format", Some(format)))
+            .into_iter(),
+        Some(arguments.iter().copied().map(BString::from)),
+        false,
+    )?;
+    let mut out = Vec::new();
+    while let Some(line) = lines.readline() {
+        if let Some(line) = line??.as_bstr() {
+            refs::push_ref(line, &mut out)?;
+        }
+    }
+    Ok(out)
+}
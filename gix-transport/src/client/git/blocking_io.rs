@@ -63,6 +63,33 @@ where
         true
     }
 
+    fn supports_command_pipelining(&self) -> bool {
+        self.negotiated_capabilities()
+            .map_or(self.state.desired_version, |(protocol, _)| protocol)
+            == Protocol::V2
+    }
+
+    fn negotiated_capabilities(&self) -> Option<(Protocol, &client::Capabilities)> {
+        self.state
+            .negotiated
+            .as_ref()
+            .map(|(protocol, capabilities)| (*protocol, capabilities))
+    }
+
+    fn requested_protocol(&self, _: client::traits::private::Token) -> Option<Protocol> {
+        Some(self.state.desired_version)
+    }
+
+    fn selected_capabilities(&self, _: client::traits::private::Token) -> Option<&[BString]> {
+        self.state.selected_capabilities.as_deref()
+    }
+
+    fn set_selected_capabilities(&mut self, _: client::traits::private::Token, capabilities: &[BString]) {
+        self.state
+            .selected_capabilities
+            .get_or_insert_with(|| capabilities.to_owned());
+    }
+
     fn configure(&mut self, _config: &dyn Any) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         Ok(())
     }
@@ -96,6 +123,8 @@ where
             refs,
             protocol: actual_protocol,
         } = Handshake::from_lines_with_version_detection(&mut self.line_provider)?;
+        self.state.negotiated = Some((actual_protocol, capabilities.clone()));
+        self.state.selected_capabilities = None;
         Ok(SetServiceResponse {
             actual_protocol,
             capabilities,
@@ -147,6 +176,8 @@ where
                 desired_version,
                 custom_url: None,
                 mode,
+                negotiated: None,
+                selected_capabilities: None,
             },
         }
     }
@@ -1,6 +1,6 @@
 use bstr::BString;
 
-use crate::Protocol;
+use crate::{client::Capabilities, Protocol};
 
 /// The way to connect to a process speaking the `git` protocol.
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -18,6 +18,10 @@ pub(crate) struct ConnectionState {
     pub(in crate::client) desired_version: Protocol,
     custom_url: Option<BString>,
     pub(in crate::client) mode: ConnectMode,
+    /// The protocol and capabilities of the last handshake.
+    pub(in crate::client) negotiated: Option<(Protocol, Capabilities)>,
+    /// The capabilities sent along with the first V2 command after the last handshake.
+    pub(in crate::client) selected_capabilities: Option<Vec<BString>>,
 }
 
 mod message {
@@ -2,7 +2,7 @@
 #[cfg(feature = "async-client")]
 pub mod async_io;
 
-mod traits;
+pub(crate) mod traits;
 pub use traits::TransportWithoutIO;
 
 ///
@@ -14,9 +14,13 @@ pub mod capabilities;
 #[doc(inline)]
 pub use capabilities::Capabilities;
 
+///
+#[cfg(feature = "blocking-client")]
+pub mod multi;
+
 mod non_io_types;
 pub use gix_sec::identity::Account;
-pub use non_io_types::{Error, MessageKind, WriteMode};
+pub use non_io_types::{Error, MessageKind, NegotiationRecord, WriteMode};
 
 ///
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
@@ -0,0 +1,70 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::client::{
+    self,
+    blocking_io::{connect, ls_refs, Transport},
+    MessageKind, TransportWithoutIO, WriteMode,
+};
+
+pub use crate::client::non_io_types::refs::Ref;
+
+/// The error returned for each remote by [`ls_remote_many()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Connect(#[from] connect::Error),
+    #[error(transparent)]
+    Transport(#[from] client::Error),
+}
+
+/// Connect to each of the given `urls` and list its references like `git ls-remote` would, using up to `concurrency`
+/// threads at a time.
+///
+/// The results are returned in the order of `urls`, and each remote is handled independently so that failing to reach
+/// one of them doesn't affect the others.
+/// A `concurrency` of 0 is treated like 1.
+pub fn ls_remote_many(urls: Vec<gix_url::Url>, concurrency: usize) -> Vec<(gix_url::Url, Result<Vec<Ref>, Error>)> {
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<_> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..concurrency.clamp(1, urls.len().max(1)))
+            .map(|_| {
+                std::thread::Builder::new()
+                    .name("gix-transport::ls-remote".into())
+                    .spawn_scoped(scope, || {
+                        let mut out = Vec::new();
+                        loop {
+                            let idx = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(url) = urls.get(idx) else {
+                                break out;
+                            };
+                            out.push((idx, ls_remote(url.clone())));
+                        }
+                    })
+                    .expect("named threads work on all platforms")
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("no panics in workers"))
+            .collect()
+    });
+    results.sort_by_key(|(idx, _)| *idx);
+    urls.into_iter()
+        .zip(results)
+        .map(|(url, (_idx, result))| (url, result))
+        .collect()
+}
+
+fn ls_remote(url: gix_url::Url) -> Result<Vec<Ref>, Error> {
+    let mut transport = connect::connect(url, Default::default())?;
+    let refs = ls_refs(&mut transport, &["peel"])?;
+    // Like `git ls-remote`, indicate the end of the interaction to let stateful remotes terminate gracefully.
+    if transport.connection_persists_across_multiple_requests() {
+        transport
+            .request(WriteMode::Binary, MessageKind::Flush, false)?
+            .into_read()
+            .map_err(client::Error::Io)?;
+    }
+    Ok(refs)
+}
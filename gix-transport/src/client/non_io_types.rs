@@ -31,6 +31,48 @@ pub enum MessageKind {
     Text(&'static [u8]),
 }
 
+/// A record of the capability negotiation with a remote, useful for audits.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NegotiationRecord {
+    /// The protocol version the client asked for, or `None` if the transport doesn't know it, which is the case for
+    /// transports implemented outside of this crate.
+    pub requested_protocol: Option<crate::Protocol>,
+    /// The protocol version the remote actually speaks.
+    pub actual_protocol: crate::Protocol,
+    /// The capabilities advertised by the remote, each formatted as `name` or `name=value`.
+    pub advertised_capabilities: Vec<String>,
+    /// The capabilities the client sent along with the first protocol V2 command after the handshake, formatted like
+    /// [`advertised_capabilities`](Self::advertised_capabilities).
+    ///
+    /// It's empty if no such command was invoked yet, and always in V1 as the client selects its capabilities
+    /// as part of the first `want` line of a fetch there. Transports implemented outside of this crate don't
+    /// record them either.
+    pub selected_capabilities: Vec<String>,
+}
+
+impl NegotiationRecord {
+    pub(crate) fn new(
+        requested_protocol: Option<crate::Protocol>,
+        actual_protocol: crate::Protocol,
+        advertised: &crate::client::Capabilities,
+        selected: Option<&[bstr::BString]>,
+    ) -> Self {
+        NegotiationRecord {
+            requested_protocol,
+            actual_protocol,
+            advertised_capabilities: advertised
+                .iter()
+                .map(|c| match c.value() {
+                    Some(value) => format!("{}={value}", c.name()),
+                    None => c.name().to_string(),
+                })
+                .collect(),
+            selected_capabilities: selected.unwrap_or_default().iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub(crate) mod connect {
     /// Options for connecting to a remote.
@@ -91,6 +133,83 @@ pub(crate) mod connect {
     }
 }
 
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub(crate) mod refs {
+    use bstr::{BStr, ByteSlice};
+
+    use crate::client::{Capabilities, Error};
+
+    /// A reference as listed by a remote.
+    #[derive(PartialEq, Eq, Debug, Hash, Clone)]
+    pub struct Ref {
+        /// The full name of the reference, like `refs/heads/main`.
+        pub name: bstr::BString,
+        /// The object the reference points to.
+        pub object: gix_hash::ObjectId,
+        /// The object an annotated tag points to after peeling, if the reference points to one.
+        pub peeled: Option<gix_hash::ObjectId>,
+    }
+
+    /// Return the object format advertised in `capabilities`, to be passed along with protocol V2 commands.
+    pub fn object_format(capabilities: &Capabilities) -> Option<String> {
+        capabilities
+            .capability("object-format")
+            .and_then(|c| c.value().map(ToString::to_string))
+    }
+
+    /// Parse a V1 ref advertisement or V2 `ls-refs` line like `<hex> <name>[ <attribute>…]` into `out`.
+    ///
+    /// Peeled V1 refs like `<name>^{}` are added to the ref preceding them, while the `capabilities^{}` line
+    /// of empty repositories is skipped.
+    pub fn push_ref(line: &BStr, out: &mut Vec<Ref>) -> Result<(), Error> {
+        let decode = |hex: &[u8]| {
+            gix_hash::ObjectId::from_hex(hex).map_err(|source| Error::DecodeObjectId {
+                line: line.into(),
+                source,
+            })
+        };
+        let mut tokens = line.trim_end().split(|b| *b == b' ');
+        let (Some(hex), Some(name)) = (tokens.next(), tokens.next()) else {
+            return Ok(());
+        };
+        if name == b"capabilities^{}" {
+            return Ok(());
+        }
+        if let Some(name) = name.strip_suffix(b"^{}") {
+            if let Some(previous) = out.last_mut().filter(|r| r.name == name) {
+                previous.peeled = Some(decode(hex)?);
+            }
+            return Ok(());
+        }
+        let peeled = tokens
+            .find_map(|attribute| attribute.strip_prefix(b"peeled:"))
+            .map(decode)
+            .transpose()?;
+        out.push(Ref {
+            name: name.into(),
+            object: decode(hex)?,
+            peeled,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub(crate) mod object_info {
+    use bstr::ByteSlice;
+
+    /// Return `true` if `line` of an `object-info` response like `<hex> <size>` reports the size of the object with `id`.
+    ///
+    /// Servers leave the size empty for objects they don't have.
+    pub fn reports_size_of(line: &[u8], id: &gix_hash::oid) -> bool {
+        let mut tokens = line.trim_end().splitn(2, |b| *b == b' ');
+        match (tokens.next(), tokens.next()) {
+            (Some(hex), Some(size)) => !size.is_empty() && gix_hash::ObjectId::from_hex(hex).is_ok_and(|hex| hex == id),
+            _ => false,
+        }
+    }
+}
+
 mod error {
     use std::ffi::OsString;
 
@@ -147,6 +266,17 @@ mod error {
         SshInvocation(SshInvocationError),
         #[error("The repository path '{path}' could be mistaken for a command-line argument")]
         AmbiguousPath { path: BString },
+        #[error("The remote sent an error on the sideband: {message:?}")]
+        Sideband { message: BString },
+        #[error("The remote does not support the {command:?} command")]
+        UnsupportedCommand { command: BString },
+        #[error("The ref advertisement was already consumed by the handshake of this connection")]
+        RefAdvertisementConsumed,
+        #[error("Could not decode the object id in ref line {line:?}")]
+        DecodeObjectId {
+            line: BString,
+            source: gix_hash::decode::Error,
+        },
     }
 
     impl crate::IsSpuriousError for Error {
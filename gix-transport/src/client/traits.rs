@@ -4,9 +4,21 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use bstr::BStr;
+use bstr::{BStr, BString};
 
-use crate::{client::Error, Protocol};
+use crate::{
+    client::{Capabilities, Error, NegotiationRecord},
+    Protocol,
+};
+
+pub(crate) mod private {
+    /// A token which can't be named outside of this crate, to keep the trait methods taking it crate-private.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Token;
+}
+
+/// The protocol V2 capabilities which are known to be commands.
+const COMMANDS: &[&str] = &["ls-refs", "fetch", "object-info", "bundle-uri"];
 
 /// This trait represents all transport related functions that don't require any input/output to be done which helps
 /// implementation to share more code across blocking and async programs.
@@ -45,6 +57,107 @@ pub trait TransportWithoutIO {
     /// to the server for most graceful termination of the connection.
     fn connection_persists_across_multiple_requests(&self) -> bool;
 
+    /// Returns true if multiple protocol V2 commands, like `ls-refs` and `fetch`, can be written to a single request
+    /// before reading any of the responses, or false otherwise.
+    /// Stateless transports and connections that want or negotiated V1 return false.
+    fn supports_command_pipelining(&self) -> bool {
+        false
+    }
+
+    /// Returns the capabilities negotiated during the last handshake if this transport is able to reuse them when
+    /// seeded into a new connection to the same remote, or `None` otherwise.
+    ///
+    /// This is useful when reconnecting after a transient failure as stateless transports can then skip the
+    /// capability advertisement, saving a round-trip.
+    /// Note that the remote may have been reconfigured in the meantime, so cached capabilities may be *stale* and
+    /// should only be reused for a short while.
+    /// Also note that skipping the advertisement skips the authentication it would trigger, so failing credentials
+    /// are only noticed when making the first request.
+    fn cached_capabilities(&self) -> Option<&Capabilities> {
+        None
+    }
+
+    /// Returns the protocol and capabilities negotiated during the last handshake, or `None` if there was none yet.
+    fn negotiated_capabilities(&self) -> Option<(Protocol, &Capabilities)> {
+        None
+    }
+
+    /// Returns a record of the capability negotiation of the last handshake, or `None` if there was none yet.
+    ///
+    /// Only the capabilities selected by the first protocol V2 command are recorded, those a V1 fetch sends with its
+    /// first `want` line are not.
+    fn negotiation_record(&self) -> Option<NegotiationRecord> {
+        let (actual_protocol, advertised) = self.negotiated_capabilities()?;
+        Some(NegotiationRecord::new(
+            self.requested_protocol(private::Token),
+            actual_protocol,
+            advertised,
+            self.selected_capabilities(private::Token),
+        ))
+    }
+
+    /// Returns the protocol version asked for during the last handshake, if known.
+    #[doc(hidden)]
+    fn requested_protocol(&self, _: private::Token) -> Option<Protocol> {
+        None
+    }
+
+    /// Returns the capabilities sent along with the first protocol V2 command after the last handshake.
+    #[doc(hidden)]
+    fn selected_capabilities(&self, _: private::Token) -> Option<&[BString]> {
+        None
+    }
+
+    /// Called by `invoke()` with the `capabilities` sent along with a protocol V2 command, each formatted as `name`
+    /// or `name=value`.
+    #[doc(hidden)]
+    fn set_selected_capabilities(&mut self, _: private::Token, _capabilities: &[BString]) {}
+
+    /// Returns the names of the commands a protocol V2 remote advertised during the last handshake, like `ls-refs` or
+    /// `fetch`, or an empty list if there was no V2 handshake yet.
+    ///
+    /// Only `ls-refs`, `fetch`, `object-info` and `bundle-uri` are known to be commands, so other advertised capabilities are
+    /// never listed here, even if they are commands added in newer versions of the protocol.
+    ///
+    /// This allows checking for support before invoking a command, as `invoke()` fails with [`Error::UnsupportedCommand`]
+    /// for any command not listed here.
+    fn supported_commands(&self) -> Vec<BString> {
+        match self.negotiated_capabilities() {
+            Some((Protocol::V2, capabilities)) => capabilities
+                .iter()
+                .map(|c| c.name())
+                .filter(|name| COMMANDS.iter().any(|c| name == c))
+                .map(ToOwned::to_owned)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Translate the [negotiated capabilities](TransportWithoutIO::negotiated_capabilities()) into environment variables
+    /// to pass to subprocesses like hooks, or return an empty list if there was no handshake yet.
+    ///
+    /// The list always contains `GITOXIDE_PROTOCOL_VERSION` and `GITOXIDE_OBJECT_FORMAT`, which is `sha1` unless the remote
+    /// advertises another one, followed by `GITOXIDE_AGENT` if the remote advertised its agent.
+    fn capabilities_as_env(&self) -> Vec<(String, String)> {
+        let Some((protocol, capabilities)) = self.negotiated_capabilities() else {
+            return Vec::new();
+        };
+        let value_of = |name: &str| {
+            capabilities
+                .capability(name)
+                .and_then(|c| c.value().map(ToString::to_string))
+        };
+        let mut env = vec![
+            ("GITOXIDE_PROTOCOL_VERSION".into(), (protocol as usize).to_string()),
+            (
+                "GITOXIDE_OBJECT_FORMAT".into(),
+                value_of("object-format").unwrap_or_else(|| "sha1".into()),
+            ),
+        ];
+        env.extend(value_of("agent").map(|agent| ("GITOXIDE_AGENT".into(), agent)));
+        env
+    }
+
     /// Pass `config` can be cast and interpreted by the implementation, as documented separately.
     ///
     /// The caller must know how that `config` data looks like for the intended implementation.
@@ -69,6 +182,42 @@ impl<T: TransportWithoutIO + ?Sized> TransportWithoutIO for Box<T> {
         self.deref().connection_persists_across_multiple_requests()
     }
 
+    fn supports_command_pipelining(&self) -> bool {
+        self.deref().supports_command_pipelining()
+    }
+
+    fn cached_capabilities(&self) -> Option<&Capabilities> {
+        self.deref().cached_capabilities()
+    }
+
+    fn negotiated_capabilities(&self) -> Option<(Protocol, &Capabilities)> {
+        self.deref().negotiated_capabilities()
+    }
+
+    fn negotiation_record(&self) -> Option<NegotiationRecord> {
+        self.deref().negotiation_record()
+    }
+
+    fn requested_protocol(&self, token: private::Token) -> Option<Protocol> {
+        self.deref().requested_protocol(token)
+    }
+
+    fn selected_capabilities(&self, token: private::Token) -> Option<&[BString]> {
+        self.deref().selected_capabilities(token)
+    }
+
+    fn set_selected_capabilities(&mut self, token: private::Token, capabilities: &[BString]) {
+        self.deref_mut().set_selected_capabilities(token, capabilities);
+    }
+
+    fn supported_commands(&self) -> Vec<BString> {
+        self.deref().supported_commands()
+    }
+
+    fn capabilities_as_env(&self) -> Vec<(String, String)> {
+        self.deref().capabilities_as_env()
+    }
+
     fn configure(&mut self, config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         self.deref_mut().configure(config)
     }
@@ -91,6 +240,42 @@ impl<T: TransportWithoutIO + ?Sized> TransportWithoutIO for &mut T {
         self.deref().connection_persists_across_multiple_requests()
     }
 
+    fn supports_command_pipelining(&self) -> bool {
+        self.deref().supports_command_pipelining()
+    }
+
+    fn cached_capabilities(&self) -> Option<&Capabilities> {
+        self.deref().cached_capabilities()
+    }
+
+    fn negotiated_capabilities(&self) -> Option<(Protocol, &Capabilities)> {
+        self.deref().negotiated_capabilities()
+    }
+
+    fn negotiation_record(&self) -> Option<NegotiationRecord> {
+        self.deref().negotiation_record()
+    }
+
+    fn requested_protocol(&self, token: private::Token) -> Option<Protocol> {
+        self.deref().requested_protocol(token)
+    }
+
+    fn selected_capabilities(&self, token: private::Token) -> Option<&[BString]> {
+        self.deref().selected_capabilities(token)
+    }
+
+    fn set_selected_capabilities(&mut self, token: private::Token, capabilities: &[BString]) {
+        self.deref_mut().set_selected_capabilities(token, capabilities);
+    }
+
+    fn supported_commands(&self) -> Vec<BString> {
+        self.deref().supported_commands()
+    }
+
+    fn capabilities_as_env(&self) -> Vec<(String, String)> {
+        self.deref().capabilities_as_env()
+    }
+
     fn configure(&mut self, config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         self.deref_mut().configure(config)
     }
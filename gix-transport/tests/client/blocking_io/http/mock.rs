@@ -6,12 +6,19 @@ use std::{
 
 use bstr::ByteVec;
 use gix_transport::{
-    client::{http, TransportWithoutIO},
+    client::{blocking_io::http, TransportWithoutIO},
     Protocol,
 };
 
 use crate::fixture_bytes;
 
+/// The http backend the tests are compiled with.
+#[cfg(feature = "http-client-curl")]
+pub type Impl = http::curl::Curl;
+/// The http backend the tests are compiled with.
+#[cfg(all(feature = "http-client-reqwest", not(feature = "http-client-curl")))]
+pub type Impl = http::reqwest::Remote;
+
 enum Command {
     ReadAndRespond(Vec<u8>),
 }
@@ -97,7 +104,7 @@ pub fn serve_and_connect(
     name: &str,
     path: &str,
     version: Protocol,
-) -> Result<(Server, http::Transport<http::Impl>), crate::Error> {
+) -> Result<(Server, http::Transport<Impl>), crate::Error> {
     let server = serve_once(name);
     let url_str = format!(
         "http://{}:{}/{}",
@@ -105,7 +112,8 @@ pub fn serve_and_connect(
         &server.addr.port(),
         path
     );
-    let client = gix_transport::client::http::connect(url_str.as_str().try_into()?, version, false);
+    let client =
+        gix_transport::client::blocking_io::http::connect::<Impl>(url_str.as_str().try_into()?, version, false);
     assert_eq!(url_str, client.to_url().as_ref());
     Ok((server, client))
 }
@@ -0,0 +1,153 @@
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use bstr::ByteSlice;
+use gix_transport::client::multi::{self, ls_remote_many};
+
+use crate::fixture_bytes;
+
+/// Serve as many git daemon requests as there are `connections`, answering with the V1 or V2 fixture depending on
+/// the requested path, and return the highest number of requests that were in flight at the same time.
+fn serve(listener: TcpListener, connections: usize) -> std::thread::JoinHandle<usize> {
+    std::thread::spawn(move || {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let handlers: Vec<_> = listener
+            .incoming()
+            .take(connections)
+            .map(|stream| {
+                let mut stream = stream.expect("client connection");
+                let (in_flight, max_in_flight) = (in_flight.clone(), max_in_flight.clone());
+                std::thread::spawn(move || {
+                    let mut len = [0; 4];
+                    stream.read_exact(&mut len).expect("packet line length");
+                    let len = usize::from_str_radix(len.to_str().expect("ascii"), 16).expect("hex");
+                    let mut request = vec![0; len - 4];
+                    stream.read_exact(&mut request).expect("connect message");
+
+                    max_in_flight.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(50));
+                    // No longer in flight once the client can make its next request after receiving our response.
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                    let fixture = if request.contains_str("/v1.git") {
+                        "v1/clone.response"
+                    } else {
+                        "v2/clone.response"
+                    };
+                    stream.write_all(&fixture_bytes(fixture)).ok();
+                    // Wait for the client to hang up to not discard what it sends, like the V2 command.
+                    std::io::copy(&mut stream, &mut std::io::sink()).ok();
+                })
+            })
+            .collect();
+        for handler in handlers {
+            handler.join().expect("no panics in handlers");
+        }
+        max_in_flight.load(Ordering::SeqCst)
+    })
+}
+
+#[test]
+fn partial_success_with_bounded_concurrency() -> crate::Result {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let closed_port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+
+    let urls = [
+        format!("git://127.0.0.1:{port}/v1.git"),
+        format!("git://127.0.0.1:{closed_port}/v1.git"),
+        format!("git://127.0.0.1:{port}/v2.git"),
+        format!("git://127.0.0.1:{port}/v1.git"),
+        format!("git://127.0.0.1:{port}/v2.git"),
+        format!("git://127.0.0.1:{port}/v1.git"),
+    ]
+    .into_iter()
+    .map(|url| gix_url::parse(url.as_str().into()))
+    .collect::<Result<Vec<_>, _>>()?;
+    let server = serve(listener, urls.len() - 1);
+
+    let concurrency = 2;
+    let results = ls_remote_many(urls.clone(), concurrency);
+    assert_eq!(
+        results.iter().map(|(url, _)| url).collect::<Vec<_>>(),
+        urls.iter().collect::<Vec<_>>(),
+        "results are in the order of the input"
+    );
+
+    let id = gix_hash::ObjectId::from_hex(b"808e50d724f604f69ab93c6da2919c014667bedb")?;
+    let expected = ["HEAD", "refs/heads/master"].map(|name| multi::Ref {
+        name: name.into(),
+        object: id,
+        peeled: None,
+    });
+    for (idx, (url, result)) in results.into_iter().enumerate() {
+        if idx == 1 {
+            assert!(
+                matches!(result, Err(multi::Error::Connect(_))),
+                "the unreachable remote fails on its own: {result:?}"
+            );
+        } else {
+            assert_eq!(result?, expected, "{url:?} lists its refs in V1 and V2 alike");
+        }
+    }
+    let max_in_flight = server.join().expect("no panics in server");
+    assert!(
+        max_in_flight <= concurrency,
+        "at most {concurrency} remotes are contacted at a time, got {max_in_flight}"
+    );
+    Ok(())
+}
+
+#[test]
+fn sessions_end_with_a_flush_packet() -> crate::Result {
+    for (fixture, flush_packets_to_send, expected_tail) in [
+        ("v1/clone.response", 1, &b"0000"[..]),
+        ("v2/clone.response", 2, &b"0009peel\n00000000"[..]),
+    ] {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = gix_url::parse(
+            format!("git://127.0.0.1:{}/repo.git", listener.local_addr()?.port())
+                .as_str()
+                .into(),
+        )?;
+        let server = std::thread::spawn(move || -> std::io::Result<Vec<u8>> {
+            let (mut stream, _) = listener.accept()?;
+            let mut len = [0; 4];
+            stream.read_exact(&mut len)?;
+            let len = usize::from_str_radix(len.to_str().expect("ascii"), 16).expect("hex");
+            stream.read_exact(&mut vec![0; len - 4])?;
+            // Only send what the client reads, as unread data makes it reset the connection when hanging up.
+            let response = fixture_bytes(fixture);
+            let end = response
+                .find_iter("0000")
+                .nth(flush_packets_to_send - 1)
+                .expect("enough flush packets")
+                + 4;
+            stream.write_all(&response[..end])?;
+            let mut received = Vec::new();
+            stream.read_to_end(&mut received)?;
+            Ok(received)
+        });
+
+        let mut results = ls_remote_many(vec![url], 1);
+        results.pop().expect("one result").1?;
+        let received = server.join().expect("no panics in server")?;
+        assert!(
+            received.ends_with(expected_tail),
+            "{fixture}: the client indicates the end of the interaction, got {:?}",
+            received.as_bstr()
+        );
+        if fixture.starts_with("v1") {
+            assert_eq!(received.as_bstr(), "0000", "only the flush is sent in V1");
+        }
+    }
+    Ok(())
+}
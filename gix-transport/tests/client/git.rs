@@ -10,12 +10,12 @@ use bstr::ByteSlice;
 use futures_lite::{AsyncBufReadExt, AsyncWriteExt, StreamExt};
 #[cfg(feature = "async-client")]
 use gix_transport::client::{
-    async_io::{Transport, TransportV2Ext},
+    async_io::{stream_response, Transport, TransportExt, TransportV2Ext},
     git::async_io::Connection,
 };
 #[cfg(feature = "blocking-client")]
 use gix_transport::client::{
-    blocking_io::{Transport, TransportV2Ext},
+    blocking_io::{stream_response, Transport, TransportExt, TransportV2Ext},
     git::blocking_io::Connection,
 };
 use gix_transport::{
@@ -43,6 +43,10 @@ async fn handshake_v1_and_request() -> crate::Result {
         c.connection_persists_across_multiple_requests(),
         "tcp connections are stateful"
     );
+    assert!(
+        !c.supports_command_pipelining(),
+        "V1 negotiations are stateful, so commands can't be pipelined"
+    );
     let c = c.custom_url(Some("anything".into()));
     assert_eq!(c.to_url().as_ref(), "anything");
     let mut c = c.custom_url(None);
@@ -276,6 +280,664 @@ async fn handshake_v2_downgrade_to_v1() -> crate::Result {
     Ok(())
 }
 
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn head_oid_v1() -> crate::Result {
+    for (fixture, expected) in [
+        ("v1/clone.response", Some("808e50d724f604f69ab93c6da2919c014667bedb")),
+        ("v1/empty.response", None),
+    ] {
+        let mut out = Vec::new();
+        let input = fixture_bytes(fixture);
+        let mut c = Connection::new(
+            input.as_slice(),
+            &mut out,
+            Protocol::V1,
+            "/foo.git",
+            Some(("example.org", None)),
+            git::ConnectMode::Daemon,
+            false,
+        );
+        let head = c.head_oid().await?;
+        assert_eq!(
+            head,
+            expected
+                .map(|hex| gix_hash::ObjectId::from_hex(hex.as_bytes()))
+                .transpose()?,
+            "{fixture}: HEAD is read from the advertisement, and an empty repository has none"
+        );
+        assert_eq!(
+            out.as_slice().as_bstr(),
+            b"002egit-upload-pack /foo.git\x00host=example.org\x00".as_bstr(),
+            "nothing but the handshake is sent"
+        );
+    }
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn head_oid_v2() -> crate::Result {
+    for (fixture, expected) in [
+        ("v2/clone.response", Some("808e50d724f604f69ab93c6da2919c014667bedb")),
+        ("v2/empty.response", None),
+    ] {
+        let mut out = Vec::new();
+        let input = fixture_bytes(fixture);
+        let mut c = Connection::new(
+            input.as_slice(),
+            &mut out,
+            Protocol::V2,
+            "/bar.git",
+            Some(("example.org", None)),
+            git::ConnectMode::Daemon,
+            false,
+        );
+        let head = c.head_oid().await?;
+        assert_eq!(
+            head,
+            expected
+                .map(|hex| gix_hash::ObjectId::from_hex(hex.as_bytes()))
+                .transpose()?,
+            "{fixture}: HEAD is listed by ls-refs, and an empty repository has none"
+        );
+        assert_eq!(
+            out.as_slice().as_bstr(),
+            b"0039git-upload-pack /bar.git\x00host=example.org\x00\x00version=2\x000014command=ls-refs
+0017object-format=sha1
+0001000csymrefs
+0014ref-prefix HEAD
+0000"
+                .as_bstr(),
+            "ls-refs is limited to HEAD"
+        );
+    }
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn head_oid_after_handshake() -> crate::Result {
+    let mut out = Vec::new();
+    let input = fixture_bytes("v2/clone.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V2,
+        "/bar.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    c.handshake(Service::UploadPack, &[]).await?;
+    let head = c.head_oid().await?;
+    assert_eq!(
+        head,
+        Some(gix_hash::ObjectId::from_hex(
+            b"808e50d724f604f69ab93c6da2919c014667bedb"
+        )?)
+    );
+    drop(c);
+    assert_eq!(
+        out.as_slice().as_bstr(),
+        b"0039git-upload-pack /bar.git\x00host=example.org\x00\x00version=2\x000014command=ls-refs
+0017object-format=sha1
+0001000csymrefs
+0014ref-prefix HEAD
+0000"
+            .as_bstr(),
+        "the negotiated V2 session is reused instead of sending another handshake"
+    );
+
+    let mut out = Vec::new();
+    let input = fixture_bytes("v1/clone.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V1,
+        "/foo.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    c.handshake(Service::UploadPack, &[]).await?;
+    let err = c.head_oid().await.expect_err("the advertisement can't be read twice");
+    assert!(matches!(err, client::Error::RefAdvertisementConsumed), "{err:?}");
+    drop(c);
+    assert_eq!(
+        out.as_slice().as_bstr(),
+        b"002egit-upload-pack /foo.git\x00host=example.org\x00".as_bstr(),
+        "no second handshake is sent"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn has_object_v2() -> crate::Result {
+    let id = gix_hash::ObjectId::from_hex(b"808e50d724f604f69ab93c6da2919c014667bedb")?;
+    for (fixture, expected) in [
+        ("v2/object-info.response", true),
+        ("v2/object-info-missing.response", false),
+    ] {
+        let mut out = Vec::new();
+        let input = fixture_bytes(fixture);
+        let mut c = Connection::new(
+            input.as_slice(),
+            &mut out,
+            Protocol::V2,
+            "/bar.git",
+            Some(("example.org", None)),
+            git::ConnectMode::Daemon,
+            false,
+        );
+        let has_object = c.has_object(id).await?;
+        assert_eq!(has_object, expected, "{fixture}: missing objects have no size");
+        assert_eq!(
+            out.as_slice().as_bstr(),
+            b"0039git-upload-pack /bar.git\x00host=example.org\x00\x00version=2\x000018command=object-info
+0017object-format=sha1
+00010009size
+0031oid 808e50d724f604f69ab93c6da2919c014667bedb
+0000"
+                .as_bstr(),
+            "only the size is requested"
+        );
+    }
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn has_object_after_head_oid() -> crate::Result {
+    let id = gix_hash::ObjectId::from_hex(b"808e50d724f604f69ab93c6da2919c014667bedb")?;
+    let mut out = Vec::new();
+    let input = fixture_bytes("v2/object-info-after-ls-refs.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V2,
+        "/bar.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    let head = c.head_oid().await?;
+    assert_eq!(head, Some(id));
+    for _ in 0..2 {
+        let has_object = c.has_object(id).await?;
+        assert!(has_object, "the negotiated session is reused");
+    }
+    drop(c);
+    assert_eq!(
+        out.as_slice().as_bstr().find_iter("git-upload-pack").count(),
+        1,
+        "only the first call performs a handshake"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn has_object_after_v1_handshake() -> crate::Result {
+    let id = gix_hash::ObjectId::from_hex(b"808e50d724f604f69ab93c6da2919c014667bedb")?;
+    let mut out = Vec::new();
+    let input = fixture_bytes("v1/clone.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V1,
+        "/foo.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    c.handshake(Service::UploadPack, &[]).await?;
+    let err = c.has_object(id).await.expect_err("V1 has no object-info");
+    assert!(
+        matches!(&err, client::Error::UnsupportedCommand { command } if command == "object-info"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn has_object_requires_object_info_capability() -> crate::Result {
+    let id = gix_hash::ObjectId::from_hex(b"808e50d724f604f69ab93c6da2919c014667bedb")?;
+    for (fixture, protocol) in [("v1/clone.response", Protocol::V1), ("v2/clone.response", Protocol::V2)] {
+        let mut out = Vec::new();
+        let input = fixture_bytes(fixture);
+        let mut c = Connection::new(
+            input.as_slice(),
+            &mut out,
+            protocol,
+            "/bar.git",
+            Some(("example.org", None)),
+            git::ConnectMode::Daemon,
+            false,
+        );
+        let err = c.has_object(id).await.expect_err("object-info isn't advertised");
+        assert!(
+            matches!(&err, client::Error::UnsupportedCommand { command } if command == "object-info"),
+            "{fixture}: {err:?}"
+        );
+    }
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn capabilities_as_env_v2_sha256() -> crate::Result {
+    let mut out = Vec::new();
+    let input = fixture_bytes("v2/sha256-handshake.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V2,
+        "/bar.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    assert!(
+        c.capabilities_as_env().is_empty(),
+        "nothing is known before the handshake"
+    );
+    c.handshake(Service::UploadPack, &[]).await?;
+    assert_eq!(
+        c.capabilities_as_env(),
+        [
+            ("GITOXIDE_PROTOCOL_VERSION", "2"),
+            ("GITOXIDE_OBJECT_FORMAT", "sha256"),
+            ("GITOXIDE_AGENT", "git/2.42.0"),
+        ]
+        .map(|(name, value)| (name.to_owned(), value.to_owned()))
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn supported_commands_guard_invoke() -> crate::Result {
+    let mut out = Vec::new();
+    let input = fixture_bytes("v2/sha256-handshake.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V2,
+        "/bar.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    assert!(
+        c.supported_commands().is_empty(),
+        "nothing is known before the handshake"
+    );
+    c.handshake(Service::UploadPack, &[]).await?;
+    assert_eq!(
+        c.supported_commands(),
+        ["ls-refs", "fetch"],
+        "capabilities like the agent or the object format aren't commands, and unknown ones aren't assumed to be"
+    );
+
+    for command in ["object-info", "agent", "future-command"] {
+        let res = c
+            .invoke(
+                command,
+                std::iter::empty::<(&str, Option<&str>)>(),
+                None::<std::iter::Empty<bstr::BString>>,
+                false,
+            )
+            .await;
+        let err = res.err().expect("only supported commands can be invoked");
+        assert!(
+            matches!(&err, client::Error::UnsupportedCommand { command: actual } if actual == command),
+            "{command}: {err:?}"
+        );
+    }
+    drop(c);
+    assert!(
+        !out.as_slice().as_bstr().contains_str("command="),
+        "nothing is sent for unsupported commands, nor for capabilities which aren't known to be commands"
+    );
+    Ok(())
+}
+
+#[test]
+fn negotiation_record_of_foreign_transports() -> crate::Result {
+    struct Foreign(client::Capabilities);
+    impl TransportWithoutIO for Foreign {
+        fn to_url(&self) -> std::borrow::Cow<'_, bstr::BStr> {
+            std::borrow::Cow::Borrowed("https://example.com/repo".into())
+        }
+
+        fn connection_persists_across_multiple_requests(&self) -> bool {
+            false
+        }
+
+        fn negotiated_capabilities(&self) -> Option<(Protocol, &client::Capabilities)> {
+            Some((Protocol::V2, &self.0))
+        }
+
+        fn configure(
+            &mut self,
+            _config: &dyn std::any::Any,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            Ok(())
+        }
+    }
+
+    let transport = Foreign(client::Capabilities::from_lines("version 2\nls-refs\n".into())?);
+    assert_eq!(
+        transport.negotiation_record(),
+        Some(client::NegotiationRecord {
+            requested_protocol: None,
+            actual_protocol: Protocol::V2,
+            advertised_capabilities: vec!["ls-refs".into()],
+            selected_capabilities: Vec::new(),
+        }),
+        "the requested protocol isn't made up if the transport can't tell"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn negotiation_record_v2() -> crate::Result {
+    let mut out = Vec::new();
+    let input = fixture_bytes("v2/empty.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V2,
+        "/bar.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    assert!(
+        c.negotiation_record().is_none(),
+        "nothing is known before the handshake"
+    );
+    c.handshake(Service::UploadPack, &[]).await?;
+    let advertised = [
+        "agent=git/2.28.0",
+        "ls-refs",
+        "fetch=shallow",
+        "server-option",
+        "object-format=sha1",
+    ]
+    .map(ToOwned::to_owned)
+    .to_vec();
+    assert_eq!(
+        c.negotiation_record(),
+        Some(client::NegotiationRecord {
+            requested_protocol: Some(Protocol::V2),
+            actual_protocol: Protocol::V2,
+            advertised_capabilities: advertised.clone(),
+            selected_capabilities: Vec::new(),
+        }),
+        "the client didn't select capabilities before invoking a command"
+    );
+
+    let mut lines = c
+        .invoke(
+            "ls-refs",
+            [("agent", Some("git/oxide")), ("object-format", Some("sha1"))].into_iter(),
+            None::<std::iter::Empty<bstr::BString>>,
+            false,
+        )
+        .await?;
+    while let Some(line) = lines.readline().await {
+        line??;
+    }
+    drop(lines);
+    let record = c.negotiation_record().expect("handshake was performed");
+    assert_eq!(
+        record.selected_capabilities,
+        ["agent=git/oxide", "object-format=sha1"],
+        "the capabilities sent with the first command are recorded"
+    );
+    assert_eq!(record.advertised_capabilities, advertised);
+
+    #[cfg(feature = "serde")]
+    assert_eq!(
+        serde_json::to_value(&record)?,
+        serde_json::json!({
+            "requested_protocol": "V2",
+            "actual_protocol": "V2",
+            "advertised_capabilities": advertised,
+            "selected_capabilities": ["agent=git/oxide", "object-format=sha1"],
+        })
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn pipelined_ls_refs_and_fetch_v2() -> crate::Result {
+    let mut out = Vec::new();
+    let input = fixture_bytes("v2/clone.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V2,
+        "/bar.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    c.handshake(Service::UploadPack, &[]).await?;
+    assert!(c.supports_command_pipelining());
+
+    let mut writer = c.request(
+        client::WriteMode::OneLfTerminatedLinePerWriteCall,
+        client::MessageKind::Flush,
+        false,
+    )?;
+    writer.write_all(b"command=ls-refs").await?;
+    writer.write_message(client::MessageKind::Delimiter).await?;
+    writer.write_all(b"ref-prefix HEAD").await?;
+    writer.write_message(client::MessageKind::Flush).await?;
+    writer.write_all(b"command=fetch").await?;
+    writer.write_message(client::MessageKind::Delimiter).await?;
+    writer
+        .write_all(b"want 808e50d724f604f69ab93c6da2919c014667bedb")
+        .await?;
+    writer.write_all(b"done").await?;
+    let mut reader = writer.into_read().await?;
+
+    let mut refs = Vec::new();
+    while let Some(line) = reader.readline().await {
+        refs.extend(line??.as_bstr().map(|line| line.trim_end().as_bstr().to_owned()));
+    }
+    assert_eq!(
+        refs,
+        [
+            "808e50d724f604f69ab93c6da2919c014667bedb HEAD symref-target:refs/heads/master",
+            "808e50d724f604f69ab93c6da2919c014667bedb refs/heads/master"
+        ],
+        "the reader stops at the end of the first response"
+    );
+    reader.reset(Protocol::V2);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    assert_eq!(line, "packfile\n", "after a reset, the second response can be read");
+    drop(reader);
+
+    assert_eq!(
+        out.as_slice().as_bstr(),
+        b"0039git-upload-pack /bar.git\x00host=example.org\x00\x00version=2\x000014command=ls-refs
+00010014ref-prefix HEAD
+00000012command=fetch
+00010032want 808e50d724f604f69ab93c6da2919c014667bedb
+0009done
+0000"
+            .as_bstr(),
+        "both commands are sent before any response is read"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn ping_v1_and_request() -> crate::Result {
+    let mut out = Vec::new();
+    let input = fixture_bytes("v1/clone.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V1,
+        "/foo.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    let elapsed = c.ping().await?;
+    assert!(
+        elapsed < std::time::Duration::from_secs(60),
+        "it measures the round-trip"
+    );
+
+    let writer = c.request(client::WriteMode::Binary, client::MessageKind::Flush, false)?;
+    let nak_line = writer
+        .into_read()
+        .await?
+        .lines()
+        .next()
+        .await
+        .expect("exactly one line")?;
+    assert_eq!(nak_line, "NAK", "the transport remains usable after the ping");
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn ping_v2_and_invoke() -> crate::Result {
+    let mut out = Vec::new();
+    let input = fixture_bytes("v2/clone.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V2,
+        "/bar.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    let elapsed = c.ping().await?;
+    assert!(
+        elapsed < std::time::Duration::from_secs(60),
+        "it measures the round-trip"
+    );
+
+    let mut reader = c
+        .invoke(
+            "fetch",
+            [("object-format", Some("sha1"))].iter().copied(),
+            Some(
+                ["want 808e50d724f604f69ab93c6da2919c014667bedb", "done"]
+                    .iter()
+                    .map(|s| s.as_bytes().as_bstr().to_owned()),
+            ),
+            false,
+        )
+        .await?;
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    assert_eq!(line, "packfile\n", "the transport remains usable after the ping");
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn ping_v2_twice_and_invoke() -> crate::Result {
+    let mut out = Vec::new();
+    let input = fixture_bytes("v2/repeated-ls-refs.response");
+    let mut c = Connection::new(
+        input.as_slice(),
+        &mut out,
+        Protocol::V2,
+        "/bar.git",
+        Some(("example.org", None)),
+        git::ConnectMode::Daemon,
+        false,
+    );
+    c.ping().await?;
+    c.ping().await?;
+
+    let mut reader = c
+        .invoke(
+            "fetch",
+            [("object-format", Some("sha1"))].iter().copied(),
+            Some(
+                ["want 808e50d724f604f69ab93c6da2919c014667bedb", "done"]
+                    .iter()
+                    .map(|s| s.as_bytes().as_bstr().to_owned()),
+            ),
+            false,
+        )
+        .await?;
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    assert_eq!(line, "packfile\n", "the transport remains usable after repeated pings");
+    drop(reader);
+    drop(c);
+    assert_eq!(
+        out.as_slice().as_bstr().find_iter("git-upload-pack").count(),
+        1,
+        "only the first ping performs a handshake"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn stream_response_demultiplexes_sidebands() -> crate::Result {
+    let mut messages = Vec::new();
+    let mut out = Vec::new();
+    let input = b"000a\x01hello0010\x02progress 1\n000a\x01world0010\x02progress 2\n0000";
+    let mut c = Connection::new(
+        &input[..],
+        &mut out,
+        Protocol::V1,
+        "/foo.git",
+        None::<(&str, _)>,
+        git::ConnectMode::Process,
+        false,
+    );
+    let reader = c
+        .request(client::WriteMode::Binary, client::MessageKind::Flush, false)?
+        .into_read()
+        .await?;
+    let mut pack = Vec::new();
+    let written = stream_response(reader, &mut pack, |text| messages.push(text.as_bstr().to_owned())).await?;
+    assert_eq!(written, 10);
+    assert_eq!(pack.as_bstr(), "helloworld", "band 1 goes to the pack sink");
+    assert_eq!(
+        messages,
+        ["progress 1", "progress 2"],
+        "band 2 goes to the progress callback"
+    );
+    Ok(())
+}
+
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn stream_response_returns_sideband_errors() -> crate::Result {
+    let mut messages = Vec::new();
+    let mut out = Vec::new();
+    let input = b"000a\x01hello0010\x02progress 1\n0011\x03fatal: boom\n000a\x01world0000";
+    let mut c = Connection::new(
+        &input[..],
+        &mut out,
+        Protocol::V1,
+        "/foo.git",
+        None::<(&str, _)>,
+        git::ConnectMode::Process,
+        false,
+    );
+    let reader = c
+        .request(client::WriteMode::Binary, client::MessageKind::Flush, false)?
+        .into_read()
+        .await?;
+    let mut pack = Vec::new();
+    let res = stream_response(reader, &mut pack, |text| messages.push(text.as_bstr().to_owned())).await;
+    match res {
+        Err(client::Error::Sideband { message }) => assert_eq!(message, "fatal: boom"),
+        res => panic!("expected a sideband error, got {res:?}"),
+    }
+    assert_eq!(pack.as_bstr(), "hello", "data after the error isn't read anymore");
+    assert_eq!(messages, ["progress 1"]);
+    Ok(())
+}
+
 #[allow(clippy::unit_arg)] // side-effect of maybe-async
 #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
 async fn handshake_v2_and_request() -> crate::Result {
@@ -311,6 +973,10 @@ async fn handshake_v2_and_request_inner() -> crate::Result {
         c.connection_persists_across_multiple_requests(),
         "tcp connections are stateful despite the protocol version"
     );
+    assert!(
+        c.supports_command_pipelining(),
+        "V2 commands can be written back to back on a persistent connection"
+    );
     let res = c.handshake(Service::UploadPack, &[]).await?;
     assert_eq!(res.actual_protocol, Protocol::V2);
     assert!(